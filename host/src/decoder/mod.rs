@@ -0,0 +1,208 @@
+//! Pluggable sample decoders with lazy, decode-ahead playback.
+//!
+//! `Decoder` is implemented per container format and selected from the file
+//! extension in [`open`]. Rather than decoding a whole file up front (which
+//! stalls the audio thread on long loops), [`StreamingSource`] runs a
+//! decoder on a worker thread and feeds a small ring buffer that the
+//! realtime callback drains from.
+
+mod mp3;
+mod ogg;
+mod resample;
+mod wav;
+
+use anyhow::{anyhow, Result};
+pub use resample::ResampleQuality;
+use resample::Resampler;
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How many decoded samples to keep buffered ahead of the playback position.
+const RING_CAPACITY: usize = 16_384;
+
+/// A source of decoded PCM audio, one container format per implementation.
+///
+/// Implementations decode in blocks rather than one sample at a time so the
+/// worker thread driving them isn't dominated by per-call overhead.
+pub trait Decoder: Send {
+    /// Number of interleaved channels in the samples this decoder produces.
+    fn channels(&self) -> u16;
+
+    /// Native sample rate of the decoded audio.
+    fn sample_rate(&self) -> u32;
+
+    /// Decode and return the next block of interleaved samples, or `None`
+    /// once the stream is exhausted.
+    fn next_frame(&mut self) -> Option<Vec<f32>>;
+}
+
+/// Pick a decoder implementation based on the file's extension.
+pub fn open(file_path: &str) -> Result<Box<dyn Decoder>> {
+    let ext = std::path::Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match ext.as_str() {
+        "wav" => Ok(Box::new(wav::WavDecoder::open(file_path)?)),
+        "mp3" => Ok(Box::new(mp3::Mp3Decoder::open(file_path)?)),
+        "ogg" => Ok(Box::new(ogg::OggDecoder::open(file_path)?)),
+        other => Err(anyhow!("Unsupported sample format: .{}", other)),
+    }
+}
+
+/// Linearly resamples already-decoded interleaved samples, exposed so other
+/// subsystems (e.g. live input capture) can match a captured buffer to the
+/// output device's rate without duplicating the interpolation math.
+pub fn resample_linear(samples: &[f32], channels: u16, source_rate: u32, target_rate: u32) -> Vec<f32> {
+    resample::linear(samples, channels, source_rate, target_rate)
+}
+
+/// Decodes the entire file to mono samples, used for waveform generation.
+///
+/// Unlike [`StreamingSource`] this drains the decoder fully; it's only meant
+/// to run on a background thread, not the realtime audio callback.
+pub fn decode_to_mono(file_path: &str) -> Result<(Vec<f32>, u32)> {
+    let mut decoder = open(file_path)?;
+    let channels = decoder.channels() as usize;
+    let sample_rate = decoder.sample_rate();
+
+    let mut mono = Vec::new();
+    while let Some(block) = decoder.next_frame() {
+        if channels > 1 {
+            mono.extend(
+                block
+                    .chunks(channels)
+                    .map(|c| c.iter().sum::<f32>() / channels as f32),
+            );
+        } else {
+            mono.extend(block);
+        }
+    }
+
+    Ok((mono, sample_rate))
+}
+
+/// Drives a [`Decoder`] on a worker thread, decode-ahead into a lock-free
+/// ring buffer that the realtime audio callback drains sample-by-sample.
+///
+/// Samples are resampled to `target_sample_rate` as they're produced so the
+/// callback never has to reason about mismatched device/source rates.
+pub struct StreamingSource {
+    ring: HeapCons<f32>,
+    decoder_done: Arc<AtomicBool>,
+    channels: u16,
+    _worker: thread::JoinHandle<()>,
+}
+
+impl StreamingSource {
+    /// Spawns the decode-ahead worker at the default (highest) resample
+    /// quality. Samples are kept interleaved at the source's native channel
+    /// count (mono or stereo) so callers can mix them with a
+    /// [`SoundTransform`](crate::SoundTransform) instead of having the
+    /// decoder collapse everything to mono.
+    pub fn spawn(decoder: Box<dyn Decoder>, target_sample_rate: u32) -> Self {
+        Self::spawn_with_quality(decoder, target_sample_rate, ResampleQuality::default())
+    }
+
+    pub fn spawn_with_quality(
+        mut decoder: Box<dyn Decoder>,
+        target_sample_rate: u32,
+        quality: ResampleQuality,
+    ) -> Self {
+        let source_channels = decoder.channels();
+        let source_rate = decoder.sample_rate();
+        let (mut producer, consumer) = HeapRb::<f32>::new(RING_CAPACITY).split();
+        let decoder_done = Arc::new(AtomicBool::new(false));
+
+        let done_worker = decoder_done.clone();
+
+        let worker = thread::spawn(move || {
+            // Carries fractional read position and trailing kernel context
+            // across blocks so resampling a continuous stream doesn't seam
+            // at every decode-ahead block boundary - unlike calling
+            // `resample::linear`/`sinc_resample` fresh per block, which
+            // restarts the phase and reads silence for neighbors that
+            // actually live in the next block.
+            let mut resampler = (source_rate != target_sample_rate)
+                .then(|| Resampler::new(source_channels, source_rate, target_sample_rate, quality));
+
+            loop {
+                if producer.vacant_len() == 0 {
+                    thread::sleep(Duration::from_millis(5));
+                    continue;
+                }
+
+                match decoder.next_frame() {
+                    Some(block) => {
+                        let resampled = match &mut resampler {
+                            Some(resampler) => resampler.process(&block),
+                            None => block,
+                        };
+                        push_all(&mut producer, resampled);
+                    }
+                    None => {
+                        if let Some(resampler) = &mut resampler {
+                            push_all(&mut producer, resampler.flush());
+                        }
+                        done_worker.store(true, Ordering::Release);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            ring: consumer,
+            decoder_done,
+            channels: source_channels,
+            _worker: worker,
+        }
+    }
+
+    /// Number of channels the buffered samples are interleaved as.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Pop the next interleaved sample, or `None` if the ring is empty.
+    pub fn pull_sample(&mut self) -> Option<f32> {
+        self.ring.try_pop()
+    }
+
+    /// Pop a full stereo frame (two interleaved samples), or `None` if
+    /// fewer than two are currently buffered. The decode-ahead worker
+    /// pushes a stereo frame's samples one at a time, so a caller that
+    /// instead popped them with two separate `pull_sample()` calls could
+    /// land between the pair and silently drop the first one - shifting
+    /// every later L/R pair by one sample for the rest of playback.
+    pub fn pull_stereo_frame(&mut self) -> Option<(f32, f32)> {
+        if self.ring.occupied_len() < 2 {
+            return None;
+        }
+        let l = self.ring.try_pop()?;
+        let r = self.ring.try_pop()?;
+        Some((l, r))
+    }
+
+    /// True once the decoder is exhausted and the ring has been drained.
+    pub fn is_finished(&self) -> bool {
+        self.decoder_done.load(Ordering::Acquire) && self.ring.is_empty()
+    }
+}
+
+/// Blocks (spinning the worker thread, never the realtime callback) until
+/// every sample has been pushed, since the decode-ahead buffer is sized
+/// generously enough that a resampled block should never exceed it.
+fn push_all(producer: &mut HeapProd<f32>, samples: Vec<f32>) {
+    for sample in samples {
+        while producer.try_push(sample).is_err() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::Decoder;
+use anyhow::Result;
+use std::fs::File;
+use std::io::BufReader;
+
+pub struct Mp3Decoder {
+    decoder: minimp3::Decoder<BufReader<File>>,
+    channels: u16,
+    sample_rate: u32,
+    // The first frame has to be decoded to learn the stream's format, so it
+    // is stashed here and handed out on the first `next_frame` call instead
+    // of being thrown away.
+    pending_first: Option<Vec<f32>>,
+}
+
+impl Mp3Decoder {
+    pub fn open(file_path: &str) -> Result<Self> {
+        let file = File::open(file_path)?;
+        let mut decoder = minimp3::Decoder::new(BufReader::new(file));
+
+        let first = decoder.next_frame()?;
+        let channels = first.channels as u16;
+        let sample_rate = first.sample_rate as u32;
+        let pending_first = first.data.iter().map(|s| *s as f32 / 32768.0).collect();
+
+        Ok(Self {
+            decoder,
+            channels,
+            sample_rate,
+            pending_first: Some(pending_first),
+        })
+    }
+}
+
+impl Decoder for Mp3Decoder {
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<f32>> {
+        if let Some(first) = self.pending_first.take() {
+            return Some(first);
+        }
+
+        match self.decoder.next_frame() {
+            Ok(frame) => Some(frame.data.iter().map(|s| *s as f32 / 32768.0).collect()),
+            Err(minimp3::Error::Eof) => None,
+            Err(_) => None,
+        }
+    }
+}
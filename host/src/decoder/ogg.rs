@@ -0,0 +1,94 @@
+use super::Decoder;
+use anyhow::{anyhow, Result};
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{CodecParameters, CODEC_TYPE_NULL};
+use symphonia::core::formats::FormatReader;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::probe::Hint;
+
+pub struct OggDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn symphonia::core::codecs::Decoder>,
+    track_id: u32,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl OggDecoder {
+    pub fn open(file_path: &str) -> Result<Self> {
+        let file = std::fs::File::open(file_path)?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        hint.with_extension("ogg");
+
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            mss,
+            &Default::default(),
+            &Default::default(),
+        )?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| anyhow!("No playable audio track in OGG file"))?;
+        let track_id = track.id;
+        let params: &CodecParameters = &track.codec_params;
+
+        let decoder =
+            symphonia::default::get_codecs().make(params, &Default::default())?;
+
+        let SignalSpec {
+            rate, channels, ..
+        } = params
+            .sample_rate
+            .zip(params.channels)
+            .map(|(rate, channels)| SignalSpec::new(rate, channels))
+            .ok_or_else(|| anyhow!("OGG track is missing sample rate/channel info"))?;
+
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+            channels: channels.count() as u16,
+            sample_rate: rate,
+        })
+    }
+}
+
+impl Decoder for OggDecoder {
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<f32>> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(_) => return None,
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            return match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let mut sample_buf =
+                        SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                    sample_buf.copy_interleaved_ref(decoded);
+                    Some(sample_buf.samples().to_vec())
+                }
+                Err(_) => continue,
+            };
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Sample-rate conversion for decoded audio blocks.
+//!
+//! `Linear` is the cheap baseline (replaces the old nearest-neighbor pick,
+//! which aliased badly on anything but a near-identity ratio). `Sinc` is a
+//! windowed-sinc convolution with a precomputed sub-sample phase table,
+//! kept affordable enough for the decode-ahead worker thread by only
+//! recomputing the table once per [`StreamingSource`](super::StreamingSource)
+//! rather than per block.
+
+/// Taps on either side of the interpolation point for the sinc kernel.
+const SINC_HALF_TAPS: usize = 8;
+const SINC_TAP_COUNT: usize = SINC_HALF_TAPS * 2;
+/// Sub-sample phases precomputed for the sinc kernel lookup table.
+const SINC_PHASES: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// `out[i] = s[idx]*(1-frac) + s[idx+1]*frac`
+    Linear,
+    /// Windowed-sinc convolution, precomputed per sub-sample phase.
+    Sinc,
+}
+
+impl Default for ResampleQuality {
+    fn default() -> Self {
+        ResampleQuality::Sinc
+    }
+}
+
+/// Precomputed windowed-sinc taps for each of [`SINC_PHASES`] sub-sample
+/// offsets, so resampling a block only needs a table lookup and a dot
+/// product rather than evaluating `sinc()` per output sample.
+pub struct SincKernelTable {
+    taps: Vec<[f32; SINC_TAP_COUNT]>,
+}
+
+impl SincKernelTable {
+    pub fn new() -> Self {
+        let taps = (0..SINC_PHASES)
+            .map(|phase| {
+                let frac = phase as f32 / SINC_PHASES as f32;
+                let mut kernel = [0.0f32; SINC_TAP_COUNT];
+                for (i, tap) in kernel.iter_mut().enumerate() {
+                    let x = (i as f32 - SINC_HALF_TAPS as f32 + 1.0) - frac;
+                    *tap = sinc(x) * hann_window(x, SINC_HALF_TAPS as f32);
+                }
+                let sum: f32 = kernel.iter().sum();
+                if sum.abs() > 1e-6 {
+                    for tap in kernel.iter_mut() {
+                        *tap /= sum;
+                    }
+                }
+                kernel
+            })
+            .collect();
+        Self { taps }
+    }
+
+    fn kernel_for_phase(&self, frac: f32) -> &[f32; SINC_TAP_COUNT] {
+        let phase = ((frac * SINC_PHASES as f32) as usize).min(SINC_PHASES - 1);
+        &self.taps[phase]
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        let px = std::f32::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Hann window, tapering the sinc kernel to zero at its edges.
+fn hann_window(x: f32, half_width: f32) -> f32 {
+    let t = (x / half_width).clamp(-1.0, 1.0);
+    0.5 + 0.5 * (std::f32::consts::PI * t).cos()
+}
+
+/// Linearly interpolated resample of an interleaved multi-channel block.
+pub fn linear(block: &[f32], channels: u16, source_rate: u32, target_rate: u32) -> Vec<f32> {
+    let channels = channels as usize;
+    let frames_in = block.len() / channels.max(1);
+    let ratio = source_rate as f32 / target_rate as f32;
+    let frames_out = (frames_in as f32 / ratio) as usize;
+
+    let mut out = Vec::with_capacity(frames_out * channels);
+    for i in 0..frames_out {
+        let pos = i as f32 * ratio;
+        let idx = pos as usize;
+        let frac = pos - idx as f32;
+        for c in 0..channels {
+            let s0 = block.get(idx * channels + c).copied().unwrap_or(0.0);
+            let s1 = block.get((idx + 1) * channels + c).copied().unwrap_or(s0);
+            out.push(s0 * (1.0 - frac) + s1 * frac);
+        }
+    }
+    out
+}
+
+/// Windowed-sinc resample of an interleaved multi-channel block using a
+/// precomputed `table`. Samples outside the block (needed for taps near
+/// its edges) are treated as silence. Only correct for a block that's the
+/// *entire* signal being resampled (e.g. an already fully-decoded buffer);
+/// for the decode-ahead streaming path, where the true neighbor samples
+/// live in the next block, use [`Resampler`] instead so the kernel reads
+/// real context rather than silence at every block boundary.
+pub fn sinc_resample(
+    block: &[f32],
+    channels: u16,
+    source_rate: u32,
+    target_rate: u32,
+    table: &SincKernelTable,
+) -> Vec<f32> {
+    let channels = channels as usize;
+    let frames_in = block.len() / channels.max(1);
+    let ratio = source_rate as f32 / target_rate as f32;
+    let frames_out = (frames_in as f32 / ratio) as usize;
+
+    let frame_sample = |frame: isize, channel: usize| -> f32 {
+        if frame < 0 || frame as usize >= frames_in {
+            0.0
+        } else {
+            block[frame as usize * channels + channel]
+        }
+    };
+
+    let mut out = Vec::with_capacity(frames_out * channels);
+    for i in 0..frames_out {
+        let pos = i as f32 * ratio;
+        let idx = pos.floor() as isize;
+        let frac = pos - idx as f32;
+        let kernel = table.kernel_for_phase(frac);
+
+        for c in 0..channels {
+            let mut acc = 0.0f32;
+            for (tap_i, tap) in kernel.iter().enumerate() {
+                let sample_frame = idx - SINC_HALF_TAPS as isize + 1 + tap_i as isize;
+                acc += frame_sample(sample_frame, c) * tap;
+            }
+            out.push(acc);
+        }
+    }
+    out
+}
+
+/// Stateful sample-rate converter for the decode-ahead streaming path.
+///
+/// [`linear`] and [`sinc_resample`] each treat the block they're handed as
+/// the whole signal, restarting the fractional read position at zero and
+/// reading silence for taps that would land in a neighboring block. Fed one
+/// decoded block at a time, that drops up to one input frame per block and
+/// puts a silence-convolved dip at every block boundary. `Resampler` instead
+/// carries the fractional position and the trailing taps of context across
+/// calls, so a block boundary reads real preceding samples instead of
+/// silence, and only emits samples whose kernel support is fully available
+/// - buffering the rest until [`process`](Resampler::process) sees more
+/// input or [`flush`](Resampler::flush) is told there's no more coming.
+pub struct Resampler {
+    channels: usize,
+    ratio: f32,
+    table: Option<SincKernelTable>,
+    /// Interleaved input frames not yet fully consumed: either their output
+    /// hasn't been produced yet, or they're kept as left-hand context for
+    /// taps near the start of the next block.
+    pending: Vec<f32>,
+    /// Position, in `pending` frames, of the next output sample.
+    next_pos: f32,
+}
+
+impl Resampler {
+    pub fn new(channels: u16, source_rate: u32, target_rate: u32, quality: ResampleQuality) -> Self {
+        let table = match quality {
+            ResampleQuality::Sinc => Some(SincKernelTable::new()),
+            ResampleQuality::Linear => None,
+        };
+        let channels = channels.max(1) as usize;
+        let half_taps = match table {
+            Some(_) => SINC_HALF_TAPS as isize,
+            None => 1,
+        };
+
+        // The left-hand guard in `drain` requires `half_taps - 1` frames of
+        // real context before the center frame. At the very start of the
+        // stream there is no such context to wait for - it's genuinely
+        // silence, the same way `flush` treats the missing right-hand taps
+        // at the true end. Pre-seed `pending` with that silence (and offset
+        // `next_pos` to match) so the guard is satisfied immediately instead
+        // of stalling output until the left context exists, which for Sinc
+        // (half_taps == 8) never happens mid-block and buffers the entire
+        // file in `pending` until EOF.
+        let leading_silence = (half_taps - 1).max(0) as usize;
+
+        Self {
+            channels,
+            ratio: source_rate as f32 / target_rate as f32,
+            table,
+            pending: vec![0.0; leading_silence * channels],
+            next_pos: leading_silence as f32,
+        }
+    }
+
+    /// Left-hand context a kernel evaluation needs before its center frame
+    /// (and, for linear, how far past it `idx + 1` needs to reach).
+    fn half_taps(&self) -> isize {
+        match self.table {
+            Some(_) => SINC_HALF_TAPS as isize,
+            None => 1,
+        }
+    }
+
+    /// Feed one newly decoded block; returns however much output the block
+    /// plus buffered context makes available with full kernel support.
+    pub fn process(&mut self, block: &[f32]) -> Vec<f32> {
+        self.pending.extend_from_slice(block);
+        self.drain(false)
+    }
+
+    /// Emits the trailing tail once the decoder is exhausted, padding
+    /// missing right-hand taps with silence - legitimate now, since it's
+    /// the real end of the stream rather than an arbitrary block edge.
+    pub fn flush(&mut self) -> Vec<f32> {
+        self.drain(true)
+    }
+
+    fn drain(&mut self, at_end: bool) -> Vec<f32> {
+        let channels = self.channels;
+        let frames_avail = self.pending.len() / channels;
+        if frames_avail == 0 {
+            return Vec::new();
+        }
+
+        let half_taps = self.half_taps();
+        let mut out = Vec::new();
+
+        loop {
+            let pos = self.next_pos;
+            let idx = pos.floor() as isize;
+
+            if at_end {
+                if idx as usize >= frames_avail {
+                    break;
+                }
+            } else if idx - half_taps + 1 < 0 || idx + half_taps >= frames_avail as isize {
+                break;
+            }
+
+            let frac = pos - idx as f32;
+            let sample_at = |frame: isize, channel: usize| -> f32 {
+                if frame < 0 || frame as usize >= frames_avail {
+                    0.0
+                } else {
+                    self.pending[frame as usize * channels + channel]
+                }
+            };
+
+            match &self.table {
+                Some(table) => {
+                    let kernel = table.kernel_for_phase(frac);
+                    for c in 0..channels {
+                        let mut acc = 0.0f32;
+                        for (tap_i, tap) in kernel.iter().enumerate() {
+                            let frame = idx - SINC_HALF_TAPS as isize + 1 + tap_i as isize;
+                            acc += sample_at(frame, c) * tap;
+                        }
+                        out.push(acc);
+                    }
+                }
+                None => {
+                    for c in 0..channels {
+                        let s0 = sample_at(idx, c);
+                        // Out of range only happens at the true end of the
+                        // stream; hold the last sample instead of fading
+                        // to silence, same as the original did.
+                        let s1 = if (idx + 1) as usize >= frames_avail {
+                            s0
+                        } else {
+                            sample_at(idx + 1, c)
+                        };
+                        out.push(s0 * (1.0 - frac) + s1 * frac);
+                    }
+                }
+            }
+
+            self.next_pos += self.ratio;
+        }
+
+        // Drop fully-consumed frames, keeping only the context the next
+        // call's earliest taps still need.
+        let keep_from = (self.next_pos.floor() as isize - half_taps + 1).clamp(0, frames_avail as isize) as usize;
+        self.pending.drain(0..keep_from * channels);
+        self.next_pos -= keep_from as f32;
+
+        out
+    }
+}
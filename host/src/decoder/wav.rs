@@ -0,0 +1,76 @@
+use super::Decoder;
+use anyhow::Result;
+use std::fs::File;
+use std::io::BufReader;
+
+/// How many frames to pull from `hound` per [`Decoder::next_frame`] call.
+const BLOCK_FRAMES: usize = 4096;
+
+pub struct WavDecoder {
+    reader: hound::WavReader<BufReader<File>>,
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+    sample_format: hound::SampleFormat,
+}
+
+impl WavDecoder {
+    pub fn open(file_path: &str) -> Result<Self> {
+        let reader = hound::WavReader::open(file_path)?;
+        let spec = reader.spec();
+        Ok(Self {
+            reader,
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+            bits_per_sample: spec.bits_per_sample,
+            sample_format: spec.sample_format,
+        })
+    }
+}
+
+impl Decoder for WavDecoder {
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<f32>> {
+        let want = BLOCK_FRAMES * self.channels as usize;
+        let mut block = Vec::with_capacity(want);
+
+        match self.sample_format {
+            hound::SampleFormat::Float => {
+                for sample in self.reader.samples::<f32>().take(want) {
+                    block.push(sample.unwrap_or(0.0));
+                }
+            }
+            hound::SampleFormat::Int => match self.bits_per_sample {
+                16 => {
+                    for sample in self.reader.samples::<i16>().take(want) {
+                        block.push(sample.unwrap_or(0) as f32 / 32768.0);
+                    }
+                }
+                24 | 32 => {
+                    let scale = if self.bits_per_sample == 24 {
+                        8_388_608.0
+                    } else {
+                        2_147_483_648.0
+                    };
+                    for sample in self.reader.samples::<i32>().take(want) {
+                        block.push(sample.unwrap_or(0) as f32 / scale);
+                    }
+                }
+                _ => return None,
+            },
+        }
+
+        if block.is_empty() {
+            None
+        } else {
+            Some(block)
+        }
+    }
+}
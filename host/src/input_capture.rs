@@ -0,0 +1,121 @@
+//! Live sampling: capture audio from an input device straight into a pad,
+//! instead of only loading existing files.
+
+use crate::decoder::Decoder;
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::Stream;
+use std::sync::{Arc, Mutex};
+
+/// Peak sample amplitude below which a captured one-shot's leading frames
+/// are considered silence and trimmed, so it triggers tightly.
+const SILENCE_THRESHOLD: f32 = 0.02;
+
+/// An open input stream pushing captured frames into a shared buffer. Kept
+/// alive only as long as the pad stays armed; dropping it (via `disarm`)
+/// tears the stream down.
+pub struct ArmedInput {
+    _stream: Stream,
+    captured: Arc<Mutex<Vec<f32>>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl ArmedInput {
+    pub fn arm() -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No input device available"))?;
+
+        let supported_config = device.default_input_config()?;
+        let channels = supported_config.channels();
+        let sample_rate = supported_config.sample_rate().0;
+        let config: cpal::StreamConfig = supported_config.into();
+
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let captured_cb = captured.clone();
+
+        let stream = device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                captured_cb.lock().unwrap().extend_from_slice(data);
+            },
+            |err| eprintln!("Input stream error: {}", err),
+            None,
+        )?;
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            captured,
+            channels,
+            sample_rate,
+        })
+    }
+
+    /// Stops capture and returns the recorded samples (interleaved, native
+    /// channel count/rate), with leading silence trimmed.
+    pub fn disarm(self) -> (Vec<f32>, u16, u32) {
+        let mut samples = self.captured.lock().unwrap().clone();
+        trim_leading_silence(&mut samples, self.channels);
+        (samples, self.channels, self.sample_rate)
+    }
+}
+
+fn trim_leading_silence(samples: &mut Vec<f32>, channels: u16) {
+    let channels = channels.max(1) as usize;
+    let first_loud_frame = samples
+        .chunks(channels)
+        .position(|frame| frame.iter().any(|s| s.abs() >= SILENCE_THRESHOLD));
+
+    if let Some(frame_index) = first_loud_frame {
+        samples.drain(0..frame_index * channels);
+    }
+}
+
+/// A [`Decoder`] over samples already resident in memory, so a freshly
+/// captured pad sample can be played back through the same decode-ahead
+/// `StreamingSource` path as file-backed decoders.
+pub struct MemoryDecoder {
+    samples: Arc<Vec<f32>>,
+    channels: u16,
+    sample_rate: u32,
+    position: usize,
+}
+
+impl MemoryDecoder {
+    pub fn new(samples: Arc<Vec<f32>>, channels: u16, sample_rate: u32) -> Self {
+        Self {
+            samples,
+            channels,
+            sample_rate,
+            position: 0,
+        }
+    }
+}
+
+impl Decoder for MemoryDecoder {
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<f32>> {
+        const BLOCK_FRAMES: usize = 4096;
+        let channels = self.channels.max(1) as usize;
+        let want = BLOCK_FRAMES * channels;
+
+        if self.position >= self.samples.len() {
+            return None;
+        }
+
+        let end = (self.position + want).min(self.samples.len());
+        let block = self.samples[self.position..end].to_vec();
+        self.position = end;
+        Some(block)
+    }
+}
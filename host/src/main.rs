@@ -3,9 +3,12 @@
 use anyhow::Result;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Stream, StreamConfig};
+use generational_arena::{Arena, Index};
+use ringbuf::traits::{Observer, Producer};
+use ringbuf::HeapProd;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::File;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
@@ -17,13 +20,128 @@ use winit::{
 };
 use wry::{WebView, http::Request};
 
+mod decoder;
+mod input_capture;
+mod recorder;
+use decoder::{Decoder, StreamingSource};
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "command", content = "payload")]
 enum AudioCommand {
     Play { pad_id: usize, file_path: String, volume: f32, pan: f32 },
     Stop { pad_id: usize },
+    StopInstance { handle: VoiceHandle },
     Load { pad_id: usize, file_path: String },
     SetMasterVolume { volume: f32 },
+    SetPan { pad_id: usize, pan: f32 },
+    SetTransform { pad_id: usize, transform: SoundTransform },
+    SetChokeGroup { pad_id: usize, group: Option<u32> },
+    StartRecording { file_path: String, bit_depth: Option<recorder::BitDepth> },
+    StopRecording,
+    ArmInput { pad_id: usize },
+    DisarmInput { pad_id: usize },
+    SetEnvelope { pad_id: usize, attack: f32, hold: f32, decay: f32, sustain: f32, release: f32 },
+}
+
+/// Handle to a single overlapping playback instance returned from `Play`,
+/// serializable so the webview can hold onto it and later target
+/// `StopInstance` at that exact voice rather than the whole pad.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct VoiceHandle {
+    index: u64,
+    generation: u64,
+}
+
+impl From<Index> for VoiceHandle {
+    fn from(index: Index) -> Self {
+        let (index, generation) = index.into_raw_parts();
+        Self {
+            index: index as u64,
+            generation,
+        }
+    }
+}
+
+impl From<VoiceHandle> for Index {
+    fn from(handle: VoiceHandle) -> Self {
+        Index::from_raw_parts(handle.index as usize, handle.generation)
+    }
+}
+
+/// Per-voice stereo mixing matrix, applied after volume to place a voice in
+/// the stereo field. Mirrors Flash/Ruffle's `SoundTransform`: each input
+/// channel can be routed to either output channel, which is what lets a
+/// plain pan knob be expressed as four gain coefficients.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct SoundTransform {
+    left_to_left: f32,
+    left_to_right: f32,
+    right_to_left: f32,
+    right_to_right: f32,
+    volume: f32,
+}
+
+impl SoundTransform {
+    /// Builds a transform from a simple `-1.0` (hard left) .. `1.0` (hard
+    /// right) pan value using a constant-power curve, so the perceived
+    /// loudness stays constant as a voice sweeps across the stereo field.
+    fn panned(pan: f32, volume: f32) -> Self {
+        let pan = pan.clamp(-1.0, 1.0);
+        let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        Self {
+            left_to_left: angle.cos(),
+            left_to_right: 0.0,
+            right_to_left: 0.0,
+            right_to_right: angle.sin(),
+            volume,
+        }
+    }
+}
+
+impl Default for SoundTransform {
+    fn default() -> Self {
+        Self::panned(0.0, 1.0)
+    }
+}
+
+/// Per-voice amplitude envelope (attack/hold/decay/sustain/release), applied
+/// on top of the `SoundTransform` gain so a voice fades in/out smoothly
+/// instead of stepping straight to full volume or silence.
+///
+/// `attack`/`hold`/`decay`/`release` are durations in seconds; `sustain` is
+/// the gain level (0.0..=1.0) held between decay and release.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Envelope {
+    attack: f32,
+    hold: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+}
+
+impl Default for Envelope {
+    /// No attack/hold/decay and a short release, just long enough to avoid
+    /// the click a sample cut off mid-waveform would otherwise produce.
+    fn default() -> Self {
+        Self {
+            attack: 0.0,
+            hold: 0.0,
+            decay: 0.0,
+            sustain: 1.0,
+            release: 0.005,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeStage {
+    Attack,
+    Hold,
+    Decay,
+    Sustain,
+    /// Entered on `stop()` rather than cutting the voice off immediately,
+    /// so playback fades out over `Envelope::release` instead of clicking.
+    Release,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -34,46 +152,189 @@ struct WaveformData {
 }
 
 struct AudioBuffer {
-    samples: Vec<f32>,
-    position: usize,
-    volume: f32,
-    playing: bool,
+    source: StreamingSource,
+    transform: SoundTransform,
+    envelope: Envelope,
+    sample_rate: u32,
+    stage: EnvelopeStage,
+    /// Samples elapsed in the current envelope stage.
+    stage_samples: u32,
+    /// Envelope gain applied to the most recent sample, remembered so
+    /// `stop()` can start the release ramp from wherever the voice
+    /// currently is rather than jumping from full volume.
+    current_level: f32,
+    release_start_level: f32,
+    finished: bool,
+    choke_group: Option<u32>,
 }
 
 impl AudioBuffer {
-    fn new(samples: Vec<f32>, volume: f32) -> Self {
+    fn new(source: StreamingSource, transform: SoundTransform, envelope: Envelope, sample_rate: u32) -> Self {
         Self {
-            samples,
-            position: 0,
-            volume,
-            playing: true,
+            source,
+            transform,
+            envelope,
+            sample_rate,
+            stage: EnvelopeStage::Attack,
+            stage_samples: 0,
+            current_level: 0.0,
+            release_start_level: 0.0,
+            finished: false,
+            choke_group: None,
+        }
+    }
+
+    /// Pulls the next input sample(s) (duplicating mono sources across both
+    /// input channels), applies the pan/transform matrix and envelope gain
+    /// to produce an output stereo pair.
+    fn next_stereo(&mut self) -> (f32, f32) {
+        if self.finished {
+            return (0.0, 0.0);
+        }
+
+        let (in_left, in_right) = if self.source.channels() == 2 {
+            match self.source.pull_stereo_frame() {
+                Some((l, r)) => (l, r),
+                None => {
+                    if self.source.is_finished() {
+                        self.finished = true;
+                    }
+                    (0.0, 0.0)
+                }
+            }
+        } else {
+            match self.source.pull_sample() {
+                Some(s) => (s, s),
+                None => {
+                    if self.source.is_finished() {
+                        self.finished = true;
+                    }
+                    (0.0, 0.0)
+                }
+            }
+        };
+
+        let gain = self.advance_envelope();
+
+        let t = &self.transform;
+        let out_left = (in_left * t.left_to_left + in_right * t.right_to_left) * t.volume * gain;
+        let out_right = (in_left * t.left_to_right + in_right * t.right_to_right) * t.volume * gain;
+        (out_left, out_right)
+    }
+
+    /// Length in samples of the current envelope stage. `Sustain` has no
+    /// fixed length (it holds until `stop()` moves to `Release`); `Release`
+    /// is floored at one sample so a zero-length release still produces a
+    /// gain rather than dividing by zero.
+    fn stage_len_samples(&self) -> u32 {
+        let sample_rate = self.sample_rate.max(1) as f32;
+        match self.stage {
+            EnvelopeStage::Attack => (self.envelope.attack * sample_rate) as u32,
+            EnvelopeStage::Hold => (self.envelope.hold * sample_rate) as u32,
+            EnvelopeStage::Decay => (self.envelope.decay * sample_rate) as u32,
+            EnvelopeStage::Sustain => u32::MAX,
+            EnvelopeStage::Release => ((self.envelope.release * sample_rate) as u32).max(1),
         }
     }
 
-    fn next_sample(&mut self) -> f32 {
-        if !self.playing || self.position >= self.samples.len() {
-            return 0.0;
+    fn advance_stage(&mut self) {
+        self.stage = match self.stage {
+            EnvelopeStage::Attack => EnvelopeStage::Hold,
+            EnvelopeStage::Hold => EnvelopeStage::Decay,
+            EnvelopeStage::Decay => EnvelopeStage::Sustain,
+            EnvelopeStage::Sustain => EnvelopeStage::Sustain,
+            EnvelopeStage::Release => EnvelopeStage::Release,
+        };
+        self.stage_samples = 0;
+    }
+
+    /// Computes this sample's envelope gain and steps the stage forward.
+    /// Zero-length attack/hold/decay stages (the default) fall through
+    /// immediately instead of each eating a sample at the wrong level.
+    fn advance_envelope(&mut self) -> f32 {
+        while self.stage_len_samples() == 0
+            && !matches!(self.stage, EnvelopeStage::Sustain | EnvelopeStage::Release)
+        {
+            self.advance_stage();
         }
-        let sample = self.samples[self.position] * self.volume;
-        self.position += 1;
-        sample
+
+        let len = self.stage_len_samples();
+        let level = match self.stage {
+            EnvelopeStage::Attack => self.stage_samples as f32 / len as f32,
+            EnvelopeStage::Hold => 1.0,
+            EnvelopeStage::Decay => {
+                let t = self.stage_samples as f32 / len as f32;
+                1.0 + (self.envelope.sustain - 1.0) * t
+            }
+            EnvelopeStage::Sustain => self.envelope.sustain,
+            EnvelopeStage::Release => (self.release_start_level * (1.0 - self.stage_samples as f32 / len as f32)).max(0.0),
+        };
+
+        self.stage_samples += 1;
+        if self.stage != EnvelopeStage::Sustain && self.stage_samples >= len {
+            if self.stage == EnvelopeStage::Release {
+                self.finished = true;
+            } else {
+                self.advance_stage();
+            }
+        }
+
+        self.current_level = level;
+        level
     }
 
     fn is_finished(&self) -> bool {
-        self.position >= self.samples.len()
+        self.finished
     }
 
+    /// Releases the voice instead of cutting it off immediately: the
+    /// envelope ramps from its current level to silence over
+    /// `Envelope::release`, so `is_finished()` only goes true once that
+    /// fade completes.
     fn stop(&mut self) {
-        self.playing = false;
+        if self.stage == EnvelopeStage::Release {
+            return;
+        }
+        self.release_start_level = self.current_level;
+        self.stage = EnvelopeStage::Release;
+        self.stage_samples = 0;
     }
 }
 
 struct AudioEngine {
     device: Device,
     config: StreamConfig,
-    buffers: Arc<Mutex<HashMap<usize, AudioBuffer>>>,
+    /// Active playback instances. A `Vec<f32>`-per-pad `HashMap` can only
+    /// hold one voice per pad, so retriggering would cut off the previous
+    /// hit; the arena lets every `Play` spawn an independent overlapping
+    /// voice, identified by a stable `Index` even as others come and go.
+    voices: Arc<Mutex<Arena<AudioBuffer>>>,
+    /// Which voices belong to which pad, so `Stop`/choke groups can find
+    /// every instance a pad has triggered.
+    pad_voices: Arc<Mutex<HashMap<usize, Vec<Index>>>>,
+    /// Choke group assigned to each pad (e.g. open/closed hi-hat), checked
+    /// on `Play` to cut off other voices in the same group.
+    pad_choke_groups: HashMap<usize, u32>,
+    /// Envelope assigned to each pad, consulted on `Play` so every new
+    /// voice for that pad starts with it. Pads with no entry get
+    /// `Envelope::default()`.
+    pad_envelopes: HashMap<usize, Envelope>,
     stream: Option<Stream>,
     master_volume: Arc<Mutex<f32>>,
+    /// Tap the realtime callback pushes the final mixed output into when a
+    /// recording is active. `None` when not recording.
+    recording_tap: Arc<Mutex<Option<HeapProd<f32>>>>,
+    /// Frames skipped whole (not split across channels) because the
+    /// recording ring was full, so the writer thread falling behind shows
+    /// up as a gap rather than desynced L/R channels.
+    dropped_recording_frames: Arc<AtomicU64>,
+    active_recording: Option<recorder::ActiveRecording>,
+    /// Pads currently capturing from the input device, keyed by pad id.
+    armed_inputs: HashMap<usize, input_capture::ArmedInput>,
+    /// Samples captured via live sampling, already downmixed/resampled to
+    /// the output device's format so `play` can feed them straight into a
+    /// `MemoryDecoder`.
+    recorded_samples: Mutex<HashMap<usize, (Arc<Vec<f32>>, u16, u32)>>,
 }
 
 impl AudioEngine {
@@ -81,19 +342,28 @@ impl AudioEngine {
         let host = cpal::default_host();
         let device = host.default_output_device()
             .ok_or_else(|| anyhow::anyhow!("No output device available"))?;
-        
+
         let config = device.default_output_config()?;
         let config: StreamConfig = config.into();
-        
-        let buffers: Arc<Mutex<HashMap<usize, AudioBuffer>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let voices = Arc::new(Mutex::new(Arena::new()));
+        let pad_voices = Arc::new(Mutex::new(HashMap::new()));
         let master_volume = Arc::new(Mutex::new(1.0f32));
-        
+
         Ok(Self {
             device,
             config,
-            buffers,
+            voices,
+            pad_voices,
+            pad_choke_groups: HashMap::new(),
+            pad_envelopes: HashMap::new(),
             stream: None,
             master_volume,
+            recording_tap: Arc::new(Mutex::new(None)),
+            dropped_recording_frames: Arc::new(AtomicU64::new(0)),
+            active_recording: None,
+            armed_inputs: HashMap::new(),
+            recorded_samples: Mutex::new(HashMap::new()),
         })
     }
 
@@ -102,77 +372,166 @@ impl AudioEngine {
             return Ok(());
         }
 
-        let buffers = self.buffers.clone();
+        let voices = self.voices.clone();
         let master_volume = self.master_volume.clone();
+        let recording_tap = self.recording_tap.clone();
+        let dropped_recording_frames = self.dropped_recording_frames.clone();
         let channels = self.config.channels as usize;
-        
+
         let stream = self.device.build_output_stream(
             &self.config,
             move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                let mut buffers = buffers.lock().unwrap();
+                let mut voices = voices.lock().unwrap();
                 let master_vol = *master_volume.lock().unwrap();
-                
+                let mut recording_tap = recording_tap.lock().unwrap();
+
                 for frame in data.chunks_mut(channels) {
-                    let mut mixed_sample = 0.0f32;
-                    
-                    // Mix all playing buffers
-                    for buffer in buffers.values_mut() {
-                        mixed_sample += buffer.next_sample();
+                    let mut left_acc = 0.0f32;
+                    let mut right_acc = 0.0f32;
+
+                    // Mix all playing voices, each panned independently
+                    for (_, voice) in voices.iter_mut() {
+                        let (l, r) = voice.next_stereo();
+                        left_acc += l;
+                        right_acc += r;
                     }
-                    
-                    // Apply master volume with 2x gain boost
-                    mixed_sample *= master_vol * 2.0;
-                    
-                    // Clamp to prevent distortion
-                    mixed_sample = mixed_sample.clamp(-1.0, 1.0);
-                    
-                    // Write to all channels
-                    for sample in frame.iter_mut() {
-                        *sample = mixed_sample;
+
+                    // Apply master volume with 2x gain boost, clamped to prevent distortion
+                    left_acc = (left_acc * master_vol * 2.0).clamp(-1.0, 1.0);
+                    right_acc = (right_acc * master_vol * 2.0).clamp(-1.0, 1.0);
+
+                    if channels >= 2 {
+                        frame[0] = left_acc;
+                        frame[1] = right_acc;
+                        for sample in frame.iter_mut().skip(2) {
+                            *sample = 0.0;
+                        }
+                    } else if let Some(sample) = frame.first_mut() {
+                        *sample = (left_acc + right_acc) * 0.5;
+                    }
+
+                    // Tee the final mixed output to the recording ring buffer, if
+                    // armed. Only ever pushes - never allocates or blocks. A frame
+                    // is pushed atomically: if the ring can't hold all of its
+                    // channels, the whole frame is dropped rather than only some
+                    // of its samples, which would desync L/R for the rest of the
+                    // take.
+                    if let Some(producer) = recording_tap.as_mut() {
+                        if producer.vacant_len() >= channels {
+                            if channels >= 2 {
+                                let _ = producer.try_push(left_acc);
+                                let _ = producer.try_push(right_acc);
+                                for _ in 2..channels {
+                                    let _ = producer.try_push(0.0);
+                                }
+                            } else {
+                                let _ = producer.try_push((left_acc + right_acc) * 0.5);
+                            }
+                        } else {
+                            dropped_recording_frames.fetch_add(1, Ordering::Relaxed);
+                        }
                     }
                 }
-                
-                // Remove finished buffers
-                buffers.retain(|_, buffer| !buffer.is_finished());
+
+                // Remove finished voices
+                voices.retain(|_, voice| !voice.is_finished());
             },
             |err| eprintln!("Audio stream error: {}", err),
             None,
         )?;
-        
+
         stream.play()?;
         self.stream = Some(stream);
         println!("Audio stream started successfully");
         Ok(())
     }
 
-    fn play(&mut self, pad_id: usize, file_path: &str, volume: f32, _pan: f32) -> Result<()> {
-        if !std::path::Path::new(file_path).exists() {
-            eprintln!("File not found: {}", file_path);
-            return Ok(());
-        }
-
+    fn play(&mut self, pad_id: usize, file_path: &str, volume: f32, pan: f32) -> Result<Index> {
         // Ensure stream is running
         self.start_stream()?;
 
-        // Load WAV file
-        let samples = load_wav_file(file_path, self.config.sample_rate)?;
-        println!("Loaded {} samples from {}", samples.len(), file_path);
-        
-        let buffer = AudioBuffer::new(samples, volume);
-        
-        let mut buffers = self.buffers.lock().unwrap();
-        buffers.insert(pad_id, buffer);
-        println!("Playing pad {} with {} active buffers", pad_id, buffers.len());
-        
-        Ok(())
+        // Decode lazily: the worker thread decode-ahead fills the ring
+        // buffer while the callback starts pulling from it immediately,
+        // so long loops don't stall playback on first trigger. An empty
+        // path means the pad's sample came from live input capture rather
+        // than disk, so it's served from the in-memory recorded buffer.
+        let decoder: Box<dyn Decoder> = if file_path.is_empty() {
+            let recorded_samples = self.recorded_samples.lock().unwrap();
+            let (samples, channels, sample_rate) = recorded_samples
+                .get(&pad_id)
+                .ok_or_else(|| anyhow::anyhow!("Pad {} has no recorded sample", pad_id))?;
+            Box::new(input_capture::MemoryDecoder::new(
+                samples.clone(),
+                *channels,
+                *sample_rate,
+            ))
+        } else {
+            if !std::path::Path::new(file_path).exists() {
+                return Err(anyhow::anyhow!("File not found: {}", file_path));
+            }
+            decoder::open(file_path)?
+        };
+        let source = StreamingSource::spawn(decoder, self.config.sample_rate);
+        println!("Streaming pad {} at {} Hz", pad_id, self.config.sample_rate);
+
+        let choke_group = self.pad_choke_groups.get(&pad_id).copied();
+        let envelope = self.pad_envelopes.get(&pad_id).copied().unwrap_or_default();
+
+        let mut voices = self.voices.lock().unwrap();
+        let mut pad_voices = self.pad_voices.lock().unwrap();
+
+        // Choking: playing a pad in a group (e.g. closed hi-hat) cuts off
+        // every other currently-playing voice tagged with that group.
+        if let Some(group) = choke_group {
+            for (_, voice) in voices.iter_mut() {
+                if voice.choke_group == Some(group) {
+                    voice.stop();
+                }
+            }
+        }
+
+        let mut voice = AudioBuffer::new(source, SoundTransform::panned(pan, volume), envelope, self.config.sample_rate);
+        voice.choke_group = choke_group;
+        let handle = voices.insert(voice);
+
+        let handles = pad_voices.entry(pad_id).or_default();
+        handles.retain(|h| voices.contains(*h));
+        handles.push(handle);
+
+        println!("Playing pad {} voice {:?} ({} active voices)", pad_id, handle, voices.len());
+
+        Ok(handle)
     }
 
+    /// Releases every currently-playing voice triggered by `pad_id`. Voices
+    /// aren't removed from the arena here - each fades out over its
+    /// envelope's release stage and the realtime callback's `retain` drops
+    /// it once that completes, so stopping a pad never clicks.
     fn stop(&mut self, pad_id: usize) {
-        let mut buffers = self.buffers.lock().unwrap();
-        if let Some(buffer) = buffers.get_mut(&pad_id) {
-            buffer.stop();
+        let mut voices = self.voices.lock().unwrap();
+        let mut pad_voices = self.pad_voices.lock().unwrap();
+        if let Some(handles) = pad_voices.remove(&pad_id) {
+            for handle in handles {
+                if let Some(voice) = voices.get_mut(handle) {
+                    voice.stop();
+                }
+            }
+        }
+    }
+
+    /// Releases a single voice instance, leaving any other overlapping
+    /// voices from the same pad untouched. See [`Self::stop`] for why the
+    /// voice isn't removed from the arena immediately.
+    fn stop_instance(&mut self, handle: Index) {
+        let mut voices = self.voices.lock().unwrap();
+        if let Some(voice) = voices.get_mut(handle) {
+            voice.stop();
+        }
+
+        let mut pad_voices = self.pad_voices.lock().unwrap();
+        for handles in pad_voices.values_mut() {
+            handles.retain(|h| *h != handle);
         }
-        buffers.remove(&pad_id);
     }
 
     fn set_master_volume(&mut self, volume: f32) {
@@ -180,63 +539,139 @@ impl AudioEngine {
         *self.master_volume.lock().unwrap() = clamped;
         println!("Master volume set to {}", clamped);
     }
-}
 
-fn load_wav_file(file_path: &str, target_sample_rate: u32) -> Result<Vec<f32>> {
-    let mut reader = hound::WavReader::open(file_path)?;
-    let spec = reader.spec();
-    
-    let samples: Vec<f32> = match spec.sample_format {
-        hound::SampleFormat::Float => {
-            reader.samples::<f32>().map(|s| s.unwrap_or(0.0)).collect()
-        }
-        hound::SampleFormat::Int => {
-            match spec.bits_per_sample {
-                16 => reader.samples::<i16>()
-                    .map(|s| s.unwrap_or(0) as f32 / 32768.0)
-                    .collect(),
-                24 => reader.samples::<i32>()
-                    .map(|s| s.unwrap_or(0) as f32 / 8388608.0)
-                    .collect(),
-                32 => reader.samples::<i32>()
-                    .map(|s| s.unwrap_or(0) as f32 / 2147483648.0)
-                    .collect(),
-                _ => return Err(anyhow::anyhow!("Unsupported bit depth")),
+    fn set_pan(&mut self, pad_id: usize, pan: f32) {
+        let mut voices = self.voices.lock().unwrap();
+        let pad_voices = self.pad_voices.lock().unwrap();
+        if let Some(handles) = pad_voices.get(&pad_id) {
+            for handle in handles {
+                if let Some(voice) = voices.get_mut(*handle) {
+                    voice.transform = SoundTransform::panned(pan, voice.transform.volume);
+                }
             }
         }
-    };
-    
-    // Convert stereo to mono if needed
-    let mono_samples: Vec<f32> = if spec.channels == 2 {
-        samples.chunks(2).map(|chunk| (chunk[0] + chunk.get(1).unwrap_or(&0.0)) / 2.0).collect()
-    } else {
-        samples
-    };
-    
-    // Simple resampling if needed
-    if spec.sample_rate != target_sample_rate {
-        let ratio = spec.sample_rate as f32 / target_sample_rate as f32;
-        let new_len = (mono_samples.len() as f32 / ratio) as usize;
-        let resampled: Vec<f32> = (0..new_len)
-            .map(|i| {
-                let pos = i as f32 * ratio;
-                let idx = pos as usize;
-                if idx < mono_samples.len() {
-                    mono_samples[idx]
-                } else {
-                    0.0
+    }
+
+    fn set_transform(&mut self, pad_id: usize, transform: SoundTransform) {
+        let mut voices = self.voices.lock().unwrap();
+        let pad_voices = self.pad_voices.lock().unwrap();
+        if let Some(handles) = pad_voices.get(&pad_id) {
+            for handle in handles {
+                if let Some(voice) = voices.get_mut(*handle) {
+                    voice.transform = transform;
                 }
-            })
-            .collect();
-        Ok(resampled)
-    } else {
-        Ok(mono_samples)
+            }
+        }
+    }
+
+    fn set_choke_group(&mut self, pad_id: usize, group: Option<u32>) {
+        match group {
+            Some(group) => {
+                self.pad_choke_groups.insert(pad_id, group);
+            }
+            None => {
+                self.pad_choke_groups.remove(&pad_id);
+            }
+        }
+    }
+
+    /// Sets the envelope used by future voices triggered on `pad_id`.
+    /// Already-playing voices keep whatever envelope they started with.
+    fn set_envelope(&mut self, pad_id: usize, envelope: Envelope) {
+        self.pad_envelopes.insert(pad_id, envelope);
+    }
+
+    fn start_recording(&mut self, file_path: String, bit_depth: recorder::BitDepth) -> Result<()> {
+        if self.active_recording.is_some() {
+            return Err(anyhow::anyhow!("Already recording"));
+        }
+
+        // Ensure stream is running so there's a callback to tee from
+        self.start_stream()?;
+
+        let (producer, active) = recorder::start_with_depth(
+            file_path,
+            self.config.sample_rate,
+            self.config.channels,
+            bit_depth,
+        );
+        *self.recording_tap.lock().unwrap() = Some(producer);
+        self.dropped_recording_frames.store(0, Ordering::Relaxed);
+        self.active_recording = Some(active);
+
+        Ok(())
+    }
+
+    fn stop_recording(&mut self) -> Result<String> {
+        // Stop feeding the writer before draining/finalizing it
+        self.recording_tap.lock().unwrap().take();
+
+        let dropped = self.dropped_recording_frames.load(Ordering::Relaxed);
+        if dropped > 0 {
+            eprintln!("Recording: writer thread fell behind, dropped {} frame(s)", dropped);
+        }
+
+        match self.active_recording.take() {
+            Some(active) => active.finish(),
+            None => Err(anyhow::anyhow!("Not currently recording")),
+        }
+    }
+
+    fn arm_input(&mut self, pad_id: usize) -> Result<()> {
+        let armed = input_capture::ArmedInput::arm()?;
+        self.armed_inputs.insert(pad_id, armed);
+        println!("Armed pad {} for input capture", pad_id);
+        Ok(())
+    }
+
+    /// Stops capture for `pad_id`, converts it to the output device's
+    /// mono/rate, and stores it as that pad's sample. Returns the final
+    /// samples and sample rate so the caller can build a waveform.
+    fn disarm_input(&mut self, pad_id: usize) -> Result<(Arc<Vec<f32>>, u32)> {
+        let armed = self
+            .armed_inputs
+            .remove(&pad_id)
+            .ok_or_else(|| anyhow::anyhow!("Pad {} is not armed for input", pad_id))?;
+        let (captured, channels, sample_rate) = armed.disarm();
+
+        let mono: Vec<f32> = if channels == 2 {
+            captured
+                .chunks(2)
+                .map(|c| (c[0] + c.get(1).copied().unwrap_or(0.0)) / 2.0)
+                .collect()
+        } else {
+            captured
+        };
+
+        let target_rate = self.config.sample_rate;
+        let resampled = if sample_rate == target_rate {
+            mono
+        } else {
+            decoder::resample_linear(&mono, 1, sample_rate, target_rate)
+        };
+
+        let samples = Arc::new(resampled);
+        self.recorded_samples
+            .lock()
+            .unwrap()
+            .insert(pad_id, (samples.clone(), 1, target_rate));
+
+        println!(
+            "Captured {} samples for pad {} at {} Hz",
+            samples.len(),
+            pad_id,
+            target_rate
+        );
+
+        Ok((samples, target_rate))
     }
 }
 
 enum AppEvent {
     FileDropped { path: String, x: f64, y: f64 },
     WaveformReady(WaveformData),
+    VoiceStarted { pad_id: usize, handle: VoiceHandle },
+    RecordingSaved { file_path: String },
 }
 
 struct App {
@@ -324,8 +759,14 @@ impl ApplicationHandler for App {
                 match command {
                     AudioCommand::Play { pad_id, file_path, volume, pan } => {
                         if let Ok(mut eng) = ipc_engine.lock() {
-                            if let Err(e) = eng.play(pad_id, &file_path, volume, pan) {
-                                eprintln!("Error playing: {}", e);
+                            match eng.play(pad_id, &file_path, volume, pan) {
+                                Ok(handle) => {
+                                    let _ = ipc_tx.send(AppEvent::VoiceStarted {
+                                        pad_id,
+                                        handle: handle.into(),
+                                    });
+                                }
+                                Err(e) => eprintln!("Error playing: {}", e),
                             }
                         }
                     },
@@ -334,65 +775,115 @@ impl ApplicationHandler for App {
                             eng.stop(pad_id);
                         }
                     },
+                    AudioCommand::StopInstance { handle } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            eng.stop_instance(handle.into());
+                        }
+                    },
+                    AudioCommand::SetChokeGroup { pad_id, group } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            eng.set_choke_group(pad_id, group);
+                        }
+                    },
+                    AudioCommand::SetEnvelope { pad_id, attack, hold, decay, sustain, release } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            eng.set_envelope(pad_id, Envelope { attack, hold, decay, sustain, release });
+                        }
+                    },
+                    AudioCommand::StartRecording { file_path, bit_depth } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            if let Err(e) = eng.start_recording(file_path, bit_depth.unwrap_or_default()) {
+                                eprintln!("Error starting recording: {}", e);
+                            }
+                        }
+                    },
+                    AudioCommand::StopRecording => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            match eng.stop_recording() {
+                                Ok(file_path) => {
+                                    let _ = ipc_tx.send(AppEvent::RecordingSaved { file_path });
+                                }
+                                Err(e) => eprintln!("Error stopping recording: {}", e),
+                            }
+                        }
+                    },
                     AudioCommand::SetMasterVolume { volume } => {
                         if let Ok(mut eng) = ipc_engine.lock() {
                             eng.set_master_volume(volume);
                         }
                     },
+                    AudioCommand::SetPan { pad_id, pan } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            eng.set_pan(pad_id, pan);
+                        }
+                    },
+                    AudioCommand::SetTransform { pad_id, transform } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            eng.set_transform(pad_id, transform);
+                        }
+                    },
+                    AudioCommand::ArmInput { pad_id } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            if let Err(e) = eng.arm_input(pad_id) {
+                                eprintln!("Error arming pad {} for input: {}", pad_id, e);
+                            }
+                        }
+                    },
+                    AudioCommand::DisarmInput { pad_id } => {
+                        if let Ok(mut eng) = ipc_engine.lock() {
+                            match eng.disarm_input(pad_id) {
+                                Ok((samples, sample_rate)) => {
+                                    let duration = samples.len() as f32 / sample_rate as f32;
+
+                                    let total_samples = samples.len();
+                                    let points = 200;
+                                    let chunk_size = (total_samples / points).max(1);
+                                    let mut peaks = Vec::with_capacity(points);
+
+                                    for chunk in samples.chunks(chunk_size) {
+                                        let max = chunk.iter().fold(0.0f32, |a, b| a.max(b.abs()));
+                                        peaks.push(max);
+                                    }
+
+                                    let data = WaveformData {
+                                        pad_id,
+                                        peaks,
+                                        duration,
+                                    };
+
+                                    let _ = ipc_tx.send(AppEvent::WaveformReady(data));
+                                }
+                                Err(e) => eprintln!("Error disarming pad {}: {}", pad_id, e),
+                            }
+                        }
+                    },
                     AudioCommand::Load { pad_id, file_path } => {
                         let tx_clone = ipc_tx.clone();
                         thread::spawn(move || {
-                            if let Ok(mut reader) = hound::WavReader::open(&file_path) {
-                                let spec = reader.spec();
-                                let duration = reader.duration() as f32 / spec.sample_rate as f32;
-                                
-                                let samples: Vec<f32> = match spec.sample_format {
-                                    hound::SampleFormat::Float => {
-                                        reader.samples::<f32>().map(|s| s.unwrap_or(0.0)).collect()
-                                    }
-                                    hound::SampleFormat::Int => {
-                                        match spec.bits_per_sample {
-                                            16 => reader.samples::<i16>()
-                                                .map(|s| s.unwrap_or(0) as f32 / 32768.0)
-                                                .collect(),
-                                            24 => reader.samples::<i32>()
-                                                .map(|s| s.unwrap_or(0) as f32 / 8388608.0)
-                                                .collect(),
-                                            32 => reader.samples::<i32>()
-                                                .map(|s| s.unwrap_or(0) as f32 / 2147483648.0)
-                                                .collect(),
-                                            _ => vec![],
-                                        }
-                                    }
-                                };
-                                
-                                // Convert stereo to mono for waveform display
-                                let mono_samples: Vec<f32> = if spec.channels == 2 {
-                                    samples.chunks(2)
-                                        .map(|chunk| (chunk[0] + chunk.get(1).unwrap_or(&0.0)) / 2.0)
-                                        .collect()
-                                } else {
-                                    samples
-                                };
-                                
+                            // Route through the same decoder subsystem used for
+                            // playback so non-WAV files (MP3/OGG) also get a
+                            // waveform instead of only WAV.
+                            if let Ok((mono_samples, sample_rate)) = decoder::decode_to_mono(&file_path) {
+                                let duration = mono_samples.len() as f32 / sample_rate as f32;
+
                                 let total_samples = mono_samples.len();
                                 let points = 200;
                                 let chunk_size = (total_samples / points).max(1);
                                 let mut peaks = Vec::with_capacity(points);
-                                
+
                                 for chunk in mono_samples.chunks(chunk_size) {
                                     let max = chunk.iter().fold(0.0f32, |a, b| a.max(b.abs()));
                                     peaks.push(max);
                                 }
-                                
+
                                 println!("Waveform generated: {} peaks, duration: {}s", peaks.len(), duration);
-                                
+
                                 let data = WaveformData {
                                     pad_id,
                                     peaks,
                                     duration
                                 };
-                                
+
                                 let _ = tx_clone.send(AppEvent::WaveformReady(data));
                             }
                         });
@@ -489,6 +980,27 @@ impl ApplicationHandler for App {
                                 let _ = webview.evaluate_script(&js);
                             }
                         }
+                    },
+                    AppEvent::VoiceStarted { pad_id, handle } => {
+                        if let Ok(json) = serde_json::to_string(&handle) {
+                            let js = format!(
+                                "window.dispatchEvent(new CustomEvent('rust-voice-started', {{ detail: {{ padId: {}, handle: {} }} }}));",
+                                pad_id, json
+                            );
+                            if let Some(webview) = &self.webview {
+                                let _ = webview.evaluate_script(&js);
+                            }
+                        }
+                    },
+                    AppEvent::RecordingSaved { file_path } => {
+                        let path_esc = file_path.replace("\\", "\\\\");
+                        let js = format!(
+                            "window.dispatchEvent(new CustomEvent('rust-recording-saved', {{ detail: {{ path: '{}' }} }}));",
+                            path_esc
+                        );
+                        if let Some(webview) = &self.webview {
+                            let _ = webview.evaluate_script(&js);
+                        }
                     }
                 }
             }
@@ -0,0 +1,126 @@
+//! Master-mix recording: tees the realtime output into a WAV file.
+//!
+//! The audio callback can't allocate or block, so it only ever pushes into
+//! a lock-free ring buffer (`ringbuf`); a dedicated writer thread drains
+//! that buffer and encodes to disk with `hound`, well outside the realtime
+//! path.
+
+use anyhow::{anyhow, Result};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// ~1.4s of stereo audio at 48kHz; generous headroom for the writer thread
+/// to fall behind the realtime callback without dropping samples.
+const RING_CAPACITY: usize = 1 << 17;
+
+/// Output bit depth for a recording. `hound` writes 24-bit samples through
+/// `i32`, using only the low 24 bits, so the two depths differ only in the
+/// spec's `bits_per_sample` and the integer scale samples are quantized to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    Sixteen,
+    TwentyFour,
+}
+
+impl Default for BitDepth {
+    fn default() -> Self {
+        BitDepth::Sixteen
+    }
+}
+
+/// A recording in progress. Dropping this without calling [`finish`] leaves
+/// the writer thread running and the file un-finalized, so callers should
+/// always route through `finish`.
+pub struct ActiveRecording {
+    stop_flag: Arc<AtomicBool>,
+    writer: Option<JoinHandle<Result<()>>>,
+    file_path: String,
+}
+
+/// Starts a writer thread encoding to `file_path` at `depth` and returns the
+/// producer half of its ring buffer for the audio callback to push into.
+pub fn start_with_depth(
+    file_path: String,
+    sample_rate: u32,
+    channels: u16,
+    depth: BitDepth,
+) -> (HeapProd<f32>, ActiveRecording) {
+    let (producer, consumer) = HeapRb::<f32>::new(RING_CAPACITY).split();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let stop_flag_writer = stop_flag.clone();
+    let writer_path = file_path.clone();
+
+    let writer =
+        thread::spawn(move || write_loop(writer_path, sample_rate, channels, depth, consumer, stop_flag_writer));
+
+    (
+        producer,
+        ActiveRecording {
+            stop_flag,
+            writer: Some(writer),
+            file_path,
+        },
+    )
+}
+
+fn write_loop(
+    file_path: String,
+    sample_rate: u32,
+    channels: u16,
+    depth: BitDepth,
+    mut consumer: HeapCons<f32>,
+    stop_flag: Arc<AtomicBool>,
+) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: match depth {
+            BitDepth::Sixteen => 16,
+            BitDepth::TwentyFour => 24,
+        },
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&file_path, spec)?;
+
+    loop {
+        let mut drained_any = false;
+        while let Some(sample) = consumer.try_pop() {
+            let sample = sample.clamp(-1.0, 1.0);
+            match depth {
+                BitDepth::Sixteen => writer.write_sample((sample * i16::MAX as f32) as i16)?,
+                BitDepth::TwentyFour => writer.write_sample((sample * 8_388_607.0) as i32)?,
+            }
+            drained_any = true;
+        }
+
+        if stop_flag.load(Ordering::Acquire) && !drained_any {
+            break;
+        }
+        if !drained_any {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+impl ActiveRecording {
+    /// Signals the writer thread to drain the remaining buffer, finalize
+    /// the WAV file, and blocks until it's done. Returns the path that was
+    /// recorded to.
+    pub fn finish(mut self) -> Result<String> {
+        self.stop_flag.store(true, Ordering::Release);
+        if let Some(writer) = self.writer.take() {
+            writer
+                .join()
+                .map_err(|_| anyhow!("Recording writer thread panicked"))??;
+        }
+        Ok(self.file_path)
+    }
+}